@@ -4,10 +4,129 @@ use tfhe::integer::RadixCiphertext;
 use tfhe::integer::ServerKey;
 use tfhe::integer::RadixClientKey;
 use tfhe::integer::gen_keys_radix;
+use tfhe::integer::wopbs::WopbsKey;
+use tfhe::shortint::parameters::ClassicPBSParameters;
+use tfhe::shortint::parameters::WopbsParameters;
+use tfhe::shortint::parameters::PARAM_MESSAGE_1_CARRY_1_KS_PBS;
 use tfhe::shortint::parameters::PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+use tfhe::shortint::parameters::PARAM_MESSAGE_3_CARRY_3_KS_PBS;
+use tfhe::shortint::parameters::PARAM_MESSAGE_4_CARRY_4_KS_PBS;
+use tfhe::shortint::parameters::parameters_wopbs_message_carry::WOPBS_PARAM_MESSAGE_1_CARRY_1_KS_PBS;
+use tfhe::shortint::parameters::parameters_wopbs_message_carry::WOPBS_PARAM_MESSAGE_2_CARRY_2_KS_PBS;
+use tfhe::shortint::parameters::parameters_wopbs_message_carry::WOPBS_PARAM_MESSAGE_3_CARRY_3_KS_PBS;
+use tfhe::shortint::parameters::parameters_wopbs_message_carry::WOPBS_PARAM_MESSAGE_4_CARRY_4_KS_PBS;
 
 use rayon::prelude::*;
 
+// The message/carry parameter set a context is built with. Each variant
+// trades off block count against per-bootstrap latency: fewer message
+// bits per block means more blocks to cover a u8, but a smaller carry
+// space to bootstrap through.
+#[derive(Clone, Copy)]
+enum CharParams {
+    Message1Carry1,
+    Message2Carry2,
+    Message3Carry3,
+    Message4Carry4,
+}
+
+impl CharParams {
+    fn shortint_params(&self) -> ClassicPBSParameters {
+        match self {
+            CharParams::Message1Carry1 => PARAM_MESSAGE_1_CARRY_1_KS_PBS,
+            CharParams::Message2Carry2 => PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+            CharParams::Message3Carry3 => PARAM_MESSAGE_3_CARRY_3_KS_PBS,
+            CharParams::Message4Carry4 => PARAM_MESSAGE_4_CARRY_4_KS_PBS,
+        }
+    }
+
+    fn wopbs_params(&self) -> WopbsParameters {
+        match self {
+            CharParams::Message1Carry1 => WOPBS_PARAM_MESSAGE_1_CARRY_1_KS_PBS,
+            CharParams::Message2Carry2 => WOPBS_PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+            CharParams::Message3Carry3 => WOPBS_PARAM_MESSAGE_3_CARRY_3_KS_PBS,
+            CharParams::Message4Carry4 => WOPBS_PARAM_MESSAGE_4_CARRY_4_KS_PBS,
+        }
+    }
+
+    fn message_bits(&self) -> u32 {
+        match self {
+            CharParams::Message1Carry1 => 1,
+            CharParams::Message2Carry2 => 2,
+            CharParams::Message3Carry3 => 3,
+            CharParams::Message4Carry4 => 4,
+        }
+    }
+
+    // Smallest block count whose message bits cover a full u8 character.
+    fn num_blocks(&self) -> usize {
+        let bits = self.message_bits();
+        ((8 + bits - 1) / bits) as usize
+    }
+}
+
+// Owns the keys a session of `FheAscii`/`FheString` operations is run
+// under, so callers can pick a parameter set once, generate (or reuse)
+// keys for it, and thread the same context through every operation
+// instead of juggling bare `ServerKey`/`RadixClientKey`/`WopbsKey` values.
+struct FheStringContext {
+    num_blocks: usize,
+    ck: RadixClientKey,
+    sk: ServerKey,
+}
+
+// A context that also carries a wopbs key, required by the LUT-based
+// transforms (`to_lower`, `is_alphabetic`, ...). Keeping this as a
+// separate type rather than an `Option<WopbsKey>` on `FheStringContext`
+// means forgetting `.build_with_wopbs()` is a compile error at the call
+// site of those methods, not a runtime panic.
+struct WopbsContext {
+    base: FheStringContext,
+    wopbs_key: WopbsKey,
+}
+
+impl std::ops::Deref for WopbsContext {
+    type Target = FheStringContext;
+
+    fn deref(&self) -> &FheStringContext {
+        return &self.base;
+    }
+}
+
+impl FheStringContext {
+    fn builder(params: CharParams) -> FheStringContextBuilder {
+        FheStringContextBuilder { params }
+    }
+}
+
+// Builder for `FheStringContext`/`WopbsContext`: the wopbs key is only
+// generated by `build_with_wopbs()`, since it's an extra, fairly expensive
+// key that LUT-based transforms need but plain comparisons and search
+// don't.
+struct FheStringContextBuilder {
+    params: CharParams,
+}
+
+impl FheStringContextBuilder {
+    fn build(self) -> FheStringContext {
+        let num_blocks = self.params.num_blocks();
+        let (ck, sk) = gen_keys_radix(self.params.shortint_params(), num_blocks);
+
+        FheStringContext { num_blocks, ck, sk }
+    }
+
+    fn build_with_wopbs(self) -> WopbsContext {
+        let num_blocks = self.params.num_blocks();
+        let (ck, sk) = gen_keys_radix(self.params.shortint_params(), num_blocks);
+        let wopbs_key = WopbsKey::new_wopbs_key(&ck, &sk, &self.params.wopbs_params());
+
+        WopbsContext {
+            base: FheStringContext { num_blocks, ck, sk },
+            wopbs_key,
+        }
+    }
+}
+
 #[derive(Clone)]
 struct FheAscii {
     block: RadixCiphertext
@@ -15,249 +134,576 @@ struct FheAscii {
 
 #[derive(Clone)]
 struct FheString {
-    blocks: Vec<FheAscii>
+    blocks: Vec<FheAscii>,
+    // Set by `encrypt_padded`: marks that `blocks.len() - 1` is a fixed
+    // capacity rather than the real terminator position, so callers that
+    // need to read the true length off the block count (`contains`,
+    // `find`, `ends_with`) can refuse a padded string instead of quietly
+    // computing a wrong answer.
+    padded: bool,
 }
 
 impl FheAscii {
-    const NUM_BLOCKS: usize = 4;
-
-    fn encrypt(c: &char, ck: &RadixClientKey) -> Self {
+    fn encrypt(c: &char, ctx: &FheStringContext) -> Self {
         FheAscii {
-            block: ck.encrypt(*c as u8),
+            block: ctx.ck.encrypt(*c as u8),
         }
     }
 
-    fn decrypt(&self, ck: &RadixClientKey) -> char {
-        return ck.decrypt::<u8>(&self.block) as char;
-    }
-
-    fn _get_num_blocks(&self) -> usize {
-        return Self::NUM_BLOCKS;
+    fn decrypt(&self, ctx: &FheStringContext) -> char {
+        return ctx.ck.decrypt::<u8>(&self.block) as char;
     }
 }
 
 impl FheString {
-    fn encrypt(string: &str, ck: &RadixClientKey) -> Self {
+    fn encrypt(string: &str, ctx: &FheStringContext) -> Self {
         let mut b: Vec<FheAscii> = vec![];
         for ch in string.chars() {
-            b.push(FheAscii::encrypt(&ch, ck));
+            b.push(FheAscii::encrypt(&ch, ctx));
         }
-        b.push(FheAscii::encrypt(&'\0', ck));
+        b.push(FheAscii::encrypt(&'\0', ctx));
 
         FheString {
             blocks: b,
+            padded: false,
         }
     }
 
-    fn decrypt(&self, ck: &RadixClientKey) -> String {
+    // Stops at the first null block, since that's how `encrypt_padded`
+    // marks the end of content within its fixed-size buffer. As a result
+    // plaintexts with an embedded '\0' (not just a trailing terminator)
+    // cannot round-trip through this representation: the real length
+    // is lost the moment the string is encrypted.
+    fn decrypt(&self, ctx: &FheStringContext) -> String {
         let mut dec: String = String::with_capacity(0);
         for b in &self.blocks {
-            let c = b.decrypt(ck);
+            let c = b.decrypt(ctx);
+            if c == '\0' {
+                break;
+            }
             dec.push(c);
         }
-        return dec[..dec.len()-1].to_string();
+        return dec;
     }
 
-    fn len(&self, sk: &ServerKey) -> RadixCiphertext {
-        let mut length = sk.create_trivial_radix(0u64,FheAscii::NUM_BLOCKS);
-        sk.smart_scalar_add_assign_parallelized(&mut length, (self.blocks.len()-1) as u64);
+    // Encrypts `string` into a buffer of exactly `max_len` blocks: the real
+    // characters, a null terminator, then null padding up to capacity. Every
+    // ciphertext of the same `max_len` has the same size on the wire, so the
+    // plaintext length no longer leaks through the number of blocks.
+    fn encrypt_padded(string: &str, max_len: usize, ctx: &FheStringContext) -> Self {
+        let char_count = string.chars().count();
+        assert!(char_count + 1 <= max_len, "string does not fit in max_len");
+
+        let mut b: Vec<FheAscii> = vec![];
+        for ch in string.chars() {
+            b.push(FheAscii::encrypt(&ch, ctx));
+        }
+        while b.len() < max_len {
+            b.push(FheAscii::encrypt(&'\0', ctx));
+        }
 
-        return length;
+        FheString { blocks: b, padded: true }
     }
 
-    fn is_empty(&self, sk: &ServerKey) -> RadixCiphertext {
-        let mut length = self.len(sk);
-        return sk.smart_scalar_eq_parallelized(&mut length, 0u64);
+    // Homomorphic length: flags each block as non-null, then folds the
+    // flags with a balanced binary tree of additions so the critical path
+    // is O(log n) rather than a sequential running sum. Works the same way
+    // whether the string was built with `encrypt` or `encrypt_padded`, and
+    // never reveals where the null terminator actually sits. Unlike
+    // `decrypt`, this does not stop at the first '\0': it counts every
+    // non-null block, so a plaintext with embedded NULs reports
+    // `chars().count()` minus the number of NULs.
+    fn len(&self, ctx: &FheStringContext) -> RadixCiphertext {
+        let sk = &ctx.sk;
+        let mut indicators: Vec<RadixCiphertext> = self.blocks.par_iter()
+            .map(|chunk| {
+                let mut block = chunk.block.clone();
+                sk.smart_scalar_ne_parallelized(&mut block, 0u64)
+            })
+            .collect();
+
+        if indicators.is_empty() {
+            return sk.create_trivial_radix(0u64, ctx.num_blocks);
+        }
+
+        while indicators.len() > 1 {
+            indicators = indicators.par_chunks(2)
+                .map(|pair| {
+                    if let [a, b] = pair {
+                        let mut a = a.clone();
+                        let mut b = b.clone();
+                        sk.smart_add_parallelized(&mut a, &mut b)
+                    } else {
+                        pair[0].clone()
+                    }
+                })
+                .collect();
+        }
+
+        return indicators.remove(0);
     }
 
-    fn to_lower_assign_parallelized(&mut self, sk: &ServerKey) -> &Self {
-        
-        self.blocks.par_iter_mut().for_each(|chunk|{
-            let (mut t1, mut t2) = rayon::join(
-                || sk.scalar_gt_parallelized(&chunk.block, 64u64),
-                || sk.scalar_lt_parallelized(&chunk.block, 91u64),
-            );
+    fn is_empty(&self, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut length = self.len(ctx);
+        return ctx.sk.smart_scalar_eq_parallelized(&mut length, 0u64);
+    }
 
-            sk.smart_bitand_assign_parallelized(&mut t1, &mut t2);
-            sk.smart_scalar_mul_assign_parallelized(&mut t1, 32u64);
-            sk.smart_add_assign_parallelized(&mut chunk.block, &mut t1);
+    // Builds the 256-entry LUT for `f` once (every block shares the same
+    // radix decomposition), then evaluates it on each block with a single
+    // wopbs bootstrap instead of a chain of comparison/arithmetic ops.
+    fn apply_lut_assign(&mut self, f: impl Fn(u8) -> u8 + Sync, ctx: &WopbsContext) -> &Self {
+        let wopbs_key = &ctx.wopbs_key;
+        let lut = match self.blocks.first() {
+            Some(b) => wopbs_key.generate_lut_radix(&b.block, |x: u64| f(x as u8) as u64),
+            None => return self,
+        };
+
+        self.blocks.par_iter_mut().for_each(|chunk| {
+            let wopbs_ct = wopbs_key.keyswitch_to_wopbs_params(&ctx.sk, &chunk.block);
+            let ct = wopbs_key.wopbs(&wopbs_ct, &lut);
+            chunk.block = wopbs_key.keyswitch_to_pbs_params(&ct);
         });
 
         return self;
     }
 
-    fn to_upper_assign_parallelized(&mut self, sk: &ServerKey) -> &Self {
-        
-        self.blocks.par_iter_mut().for_each(|chunk|{
-            let (mut t1, mut t2) = rayon::join(
-                || sk.scalar_gt_parallelized(&chunk.block, 96u64),
-                || sk.scalar_lt_parallelized(&chunk.block, 123u64),
-            );
+    // Same as `apply_lut_assign`, but for predicates that classify a
+    // character rather than transform it: returns one 0/1 ciphertext per
+    // block instead of mutating the string in place.
+    fn classify_lut(&self, f: impl Fn(u8) -> bool + Sync, ctx: &WopbsContext) -> Vec<RadixCiphertext> {
+        let wopbs_key = &ctx.wopbs_key;
+        let lut = match self.blocks.first() {
+            Some(b) => wopbs_key.generate_lut_radix(&b.block, |x: u64| f(x as u8) as u64),
+            None => return vec![],
+        };
+
+        self.blocks.par_iter().map(|chunk| {
+            let wopbs_ct = wopbs_key.keyswitch_to_wopbs_params(&ctx.sk, &chunk.block);
+            let ct = wopbs_key.wopbs(&wopbs_ct, &lut);
+            wopbs_key.keyswitch_to_pbs_params(&ct)
+        }).collect()
+    }
 
-            sk.smart_bitand_assign_parallelized(&mut t1, &mut t2);
-            sk.smart_scalar_mul_assign_parallelized(&mut t1, 32u64);
-            sk.smart_sub_assign_parallelized(&mut chunk.block,&mut t1);
-        });
+    fn to_lower_assign_parallelized(&mut self, ctx: &WopbsContext) -> &Self {
+        self.apply_lut_assign(
+            |c| if c >= b'A' && c <= b'Z' { c + 32 } else { c },
+            ctx,
+        )
+    }
 
-        return self;
+    fn to_upper_assign_parallelized(&mut self, ctx: &WopbsContext) -> &Self {
+        self.apply_lut_assign(
+            |c| if c >= b'a' && c <= b'z' { c - 32 } else { c },
+            ctx,
+        )
     }
 
-    fn equals(&self, cipher: &FheString, sk: &ServerKey) -> RadixCiphertext{
-        let mut res = sk.create_trivial_radix(1u64, FheAscii::NUM_BLOCKS);
+    fn to_ascii_rot13_assign(&mut self, ctx: &WopbsContext) -> &Self {
+        self.apply_lut_assign(
+            |c| match c {
+                b'A'..=b'Z' => b'A' + (c - b'A' + 13) % 26,
+                b'a'..=b'z' => b'a' + (c - b'a' + 13) % 26,
+                _ => c,
+            },
+            ctx,
+        )
+    }
 
-        let mut iter1 = self.blocks.iter();
-        let mut iter2 = cipher.blocks.iter();
+    fn is_alphabetic(&self, ctx: &WopbsContext) -> Vec<RadixCiphertext> {
+        self.classify_lut(|c| c.is_ascii_alphabetic(), ctx)
+    }
 
-        loop {
-            match (iter1.next(), iter2.next()) {
-                (Some(item1), Some(item2)) => {
-                    let mut ip1 = item1.block.clone();
-                    let mut ip2 = item2.block.clone();
+    fn is_digit(&self, ctx: &WopbsContext) -> Vec<RadixCiphertext> {
+        self.classify_lut(|c| c.is_ascii_digit(), ctx)
+    }
 
-                    let mut temp = sk.smart_eq_parallelized(&mut ip1, &mut ip2);
-                    sk.smart_mul_assign_parallelized(&mut res, &mut temp);
-                }
-                (None, None) => return res,
-                _ => return sk.create_trivial_radix(0u64, FheAscii::NUM_BLOCKS),
-            }
+    fn is_whitespace(&self, ctx: &WopbsContext) -> Vec<RadixCiphertext> {
+        self.classify_lut(|c| c.is_ascii_whitespace(), ctx)
+    }
+
+    // Folds per-position equality bits into a single boolean with a
+    // balanced binary tree of AND operations, so the critical path is
+    // O(log n) bootstraps instead of a sequential chain of multiplications.
+    fn tree_and(mut bits: Vec<RadixCiphertext>, ctx: &FheStringContext) -> RadixCiphertext {
+        let sk = &ctx.sk;
+        if bits.is_empty() {
+            return sk.create_trivial_radix(1u64, ctx.num_blocks);
+        }
+
+        while bits.len() > 1 {
+            bits = bits.par_chunks(2)
+                .map(|pair| {
+                    if let [a, b] = pair {
+                        let mut a = a.clone();
+                        let mut b = b.clone();
+                        sk.smart_bitand_parallelized(&mut a, &mut b)
+                    } else {
+                        pair[0].clone()
+                    }
+                })
+                .collect();
         }
+
+        return bits.remove(0);
     }
 
-    fn equals_plain(&self, string: &str, sk: &ServerKey) -> RadixCiphertext {
-        let mut res = sk.create_trivial_radix(1u64, FheAscii::NUM_BLOCKS);
+    // Compares content out to the longer side's block count, treating a
+    // missing block on either side as null (the same convention `compare`
+    // uses). This way two strings holding the same plaintext but encrypted
+    // at different `encrypt_padded` capacities (or one padded, one not)
+    // still compare equal instead of being rejected on raw block-vector
+    // length before a single character is even looked at.
+    fn equals(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext{
+        let sk = &ctx.sk;
+        let len = self.blocks.len().max(cipher.blocks.len());
+        let null_block = || sk.create_trivial_radix(0u64, ctx.num_blocks);
+
+        let bits: Vec<RadixCiphertext> = (0..len).into_par_iter()
+            .map(|i| {
+                let mut ip1 = self.blocks.get(i).map(|b| b.block.clone()).unwrap_or_else(null_block);
+                let mut ip2 = cipher.blocks.get(i).map(|b| b.block.clone()).unwrap_or_else(null_block);
+                sk.smart_eq_parallelized(&mut ip1, &mut ip2)
+            })
+            .collect();
+
+        return Self::tree_and(bits, ctx);
+    }
 
-        let mut iter1 = self.blocks.iter();
-        let mut iter2 = string.chars();
+    fn equals_plain(&self, string: &str, ctx: &FheStringContext) -> RadixCiphertext {
+        let sk = &ctx.sk;
+        let chars: Vec<char> = string.chars().collect();
+        let len = self.blocks.len().max(chars.len());
+
+        let bits: Vec<RadixCiphertext> = (0..len).into_par_iter()
+            .map(|i| {
+                let mut ip1 = self.blocks.get(i).map(|b| b.block.clone())
+                    .unwrap_or_else(|| sk.create_trivial_radix(0u64, ctx.num_blocks));
+                let item2 = chars.get(i).copied().unwrap_or('\0');
+                sk.smart_scalar_eq_parallelized(&mut ip1, item2 as u64)
+            })
+            .collect();
+
+        return Self::tree_and(bits, ctx);
+    }
 
-        loop {
-            match (iter1.next(), iter2.next()) {
-                (Some(item1), Some(item2)) => {
-                    let mut ip1 = item1.block.clone();
+    fn not_equals(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut one = ctx.sk.create_trivial_radix(1u64, ctx.num_blocks);
+        let mut eq = self.equals(cipher, ctx);
+        return ctx.sk.smart_sub_parallelized(&mut one, &mut eq);
+    }
 
-                    let mut temp = sk.smart_scalar_eq_parallelized(&mut ip1, item2 as u64);
-                    sk.smart_mul_assign_parallelized(&mut res, &mut temp);
-                }
-                (None, None) => return res,
-                _ => return sk.create_trivial_radix(0u64, FheAscii::NUM_BLOCKS),
-            }
+    fn not_equals_plain(&self, string: &str, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut one = ctx.sk.create_trivial_radix(1u64, ctx.num_blocks);
+        let mut eq = self.equals_plain(string, ctx);
+        return ctx.sk.smart_sub_parallelized(&mut one, &mut eq);
+    }
+
+    // A three-way result of -1/0/1 is carried as a wrapping radix value
+    // over `ctx.num_blocks` blocks: -1 is the all-ones value of that radix
+    // (255 when the blocks hold 8 message bits, 511 for `Message3Carry3`,
+    // whose 3 blocks hold 9). `lt`/`ge` therefore test for any value above
+    // `COMPARE_GREATER` rather than for one fixed constant.
+    const COMPARE_GREATER: u64 = 1u64;
+
+    // Encrypted lexicographic three-way compare. Missing characters on the
+    // shorter side are treated as null (0). Walks positions most-significant
+    // first, keeping a running "all-equal-so-far" bit: while it's true the
+    // running result is replaced by the local comparison at each position
+    // (multiply-select), and once a difference is found the bit flips to
+    // false and locks the result in for the rest of the walk.
+    fn compare(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        let sk = &ctx.sk;
+        let len = self.blocks.len().max(cipher.blocks.len());
+        let null_block = || sk.create_trivial_radix(0u64, ctx.num_blocks);
+
+        let mut result = null_block();
+        let mut still_equal = sk.create_trivial_radix(1u64, ctx.num_blocks);
+
+        for i in 0..len {
+            let a = self.blocks.get(i).map(|b| b.block.clone()).unwrap_or_else(null_block);
+            let b = cipher.blocks.get(i).map(|b| b.block.clone()).unwrap_or_else(null_block);
+
+            let mut lt = sk.smart_lt_parallelized(&mut a.clone(), &mut b.clone());
+            let mut gt = sk.smart_gt_parallelized(&mut a.clone(), &mut b.clone());
+            let mut local = sk.smart_sub_parallelized(&mut gt, &mut lt);
+
+            let mut diff = sk.smart_sub_parallelized(&mut local.clone(), &mut result.clone());
+            let mut selected = sk.smart_mul_parallelized(&mut still_equal.clone(), &mut diff);
+            result = sk.smart_add_parallelized(&mut result, &mut selected);
+
+            let mut local_is_zero = sk.smart_scalar_eq_parallelized(&mut local, 0u64);
+            still_equal = sk.smart_bitand_parallelized(&mut still_equal, &mut local_is_zero);
         }
+
+        return result;
     }
 
-    fn not_equals(&self, cipher: &FheString, sk: &ServerKey) -> RadixCiphertext {
-        let mut one = sk.create_trivial_radix(1u64, FheAscii::NUM_BLOCKS);
-        let mut eq = self.equals(cipher, sk);
-        return sk.smart_sub_parallelized(&mut one, &mut eq);
+    fn lt(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut c = self.compare(cipher, ctx);
+        return ctx.sk.smart_scalar_gt_parallelized(&mut c, Self::COMPARE_GREATER);
     }
-    
-    fn not_equals_plain(&self, string: &str, sk: &ServerKey) -> RadixCiphertext {
-        let mut one = sk.create_trivial_radix(1u64, FheAscii::NUM_BLOCKS);
-        let mut eq = self.equals_plain(string, sk);
-        return sk.smart_sub_parallelized(&mut one, &mut eq);
+
+    fn gt(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut c = self.compare(cipher, ctx);
+        return ctx.sk.smart_scalar_eq_parallelized(&mut c, Self::COMPARE_GREATER);
     }
 
-    fn concat_assign(&mut self, cipher: &FheString) -> &Self {
-        self.blocks.extend(cipher.blocks.clone());
-        return self;
+    fn le(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut c = self.compare(cipher, ctx);
+        return ctx.sk.smart_scalar_ne_parallelized(&mut c, Self::COMPARE_GREATER);
     }
 
-    fn starts_with(&self, cipher: &FheString, sk: &ServerKey) 
-        -> RadixCiphertext {
-        let mut res = sk.create_trivial_radix(1u64, FheAscii::NUM_BLOCKS);
+    fn ge(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        let mut c = self.compare(cipher, ctx);
+        return ctx.sk.smart_scalar_le_parallelized(&mut c, Self::COMPARE_GREATER);
+    }
 
-        let mut iter1 = self.blocks.iter();
-        let mut iter2 = cipher.blocks.iter().peekable();
+    // Same tree shape as `tree_and`, but folding with OR: used to collapse
+    // the per-offset window match bits from a sliding-window search into a
+    // single "found anywhere" boolean.
+    fn tree_or(mut bits: Vec<RadixCiphertext>, ctx: &FheStringContext) -> RadixCiphertext {
+        let sk = &ctx.sk;
+        if bits.is_empty() {
+            return sk.create_trivial_radix(0u64, ctx.num_blocks);
+        }
 
-        loop {
-            if let Some(&ref current) = iter2.next() {
-                if let Some(&_next) = iter2.peek() {
-                
-                    let mut ip1 = current.block.clone();
-                    if let Some(&ref c1) = iter1.next(){
+        while bits.len() > 1 {
+            bits = bits.par_chunks(2)
+                .map(|pair| {
+                    if let [a, b] = pair {
+                        let mut a = a.clone();
+                        let mut b = b.clone();
+                        sk.smart_bitor_parallelized(&mut a, &mut b)
+                    } else {
+                        pair[0].clone()
+                    }
+                })
+                .collect();
+        }
 
-                        let mut ip2 = c1.block.clone();
+        return bits.remove(0);
+    }
 
-                        let mut temp = sk.smart_eq_parallelized(&mut ip1, &mut ip2);
-                        sk.smart_mul_assign_parallelized(&mut res, &mut temp);
+    // For every candidate start offset `j` in `self`, compute the
+    // window-equality bit against `needle` by tree-reducing the
+    // per-character equalities (reusing `tree_and`). One bit per valid
+    // offset, computed in parallel across offsets.
+    fn match_bits(&self, needle: &[RadixCiphertext], ctx: &FheStringContext) -> Vec<RadixCiphertext> {
+        let sk = &ctx.sk;
+        let hay_len = self.blocks.len();
+        let needle_len = needle.len();
+        if needle_len > hay_len {
+            return vec![];
+        }
 
-                    }else{
-                        return sk.create_trivial_radix(0u64, FheAscii::NUM_BLOCKS);
-                    }
-                }else{
-                    return res;
-                }
-            }else {
-                return sk.create_trivial_radix(0u64, FheAscii::NUM_BLOCKS);
-            }
+        (0..=hay_len - needle_len).into_par_iter().map(|j| {
+            let bits: Vec<RadixCiphertext> = (0..needle_len).map(|k| {
+                let mut a = self.blocks[j + k].block.clone();
+                let mut b = needle[k].clone();
+                sk.smart_eq_parallelized(&mut a, &mut b)
+            }).collect();
+            Self::tree_and(bits, ctx)
+        }).collect()
+    }
+
+    // Scans the per-offset match bits left to right, selecting the first
+    // offset whose bit is set via a running "already found" flag and
+    // multiply-select. Defaults to the sentinel (haystack capacity) when
+    // no offset matches, the encrypted equivalent of `None`.
+    fn find_from_bits(&self, bits: Vec<RadixCiphertext>, ctx: &FheStringContext) -> RadixCiphertext {
+        let sk = &ctx.sk;
+        let sentinel = self.blocks.len() as u64;
+        let mut index = sk.create_trivial_radix(sentinel, ctx.num_blocks);
+        let mut found = sk.create_trivial_radix(0u64, ctx.num_blocks);
+
+        for (j, mut m) in bits.into_iter().enumerate() {
+            let mut not_found = sk.smart_scalar_eq_parallelized(&mut found.clone(), 0u64);
+            let mut take = sk.smart_bitand_parallelized(&mut m, &mut not_found);
+
+            let mut j_ct = sk.create_trivial_radix(j as u64, ctx.num_blocks);
+            let mut diff = sk.smart_sub_parallelized(&mut j_ct, &mut index.clone());
+            let mut selected = sk.smart_mul_parallelized(&mut take.clone(), &mut diff);
+            index = sk.smart_add_parallelized(&mut index, &mut selected);
+
+            found = sk.smart_bitor_parallelized(&mut found, &mut take);
         }
+
+        return index;
     }
 
-    fn starts_with_plain(&self, string: &str, sk: &ServerKey) 
-        -> RadixCiphertext {
-        let mut res = sk.create_trivial_radix(1u64, FheAscii::NUM_BLOCKS);
+    fn contains_plain(&self, string: &str, ctx: &FheStringContext) -> RadixCiphertext {
+        let needle: Vec<RadixCiphertext> = string.chars()
+            .map(|c| ctx.sk.create_trivial_radix(c as u64, ctx.num_blocks))
+            .collect();
 
-        let mut iter1 = self.blocks.iter();
-        let mut iter2 = string.chars().peekable();
+        return Self::tree_or(self.match_bits(&needle, ctx), ctx);
+    }
 
-        loop {
-            if let Some(current) = iter2.next() {
-                if let Some(&_next) = iter2.peek() {
-                
-                    let ip1 = current as u64;
-                    if let Some(&ref c1) = iter1.next(){
+    fn find_plain(&self, string: &str, ctx: &FheStringContext) -> RadixCiphertext {
+        let needle: Vec<RadixCiphertext> = string.chars()
+            .map(|c| ctx.sk.create_trivial_radix(c as u64, ctx.num_blocks))
+            .collect();
 
-                        let mut ip2 = c1.block.clone();
+        let bits = self.match_bits(&needle, ctx);
+        return self.find_from_bits(bits, ctx);
+    }
 
-                        let mut temp = sk.smart_scalar_eq_parallelized(&mut ip2, ip1);
-                        sk.smart_mul_assign_parallelized(&mut res, &mut temp);
+    // `cipher.blocks.len() - 1` is only the needle's true length when
+    // `cipher` was built with `encrypt` (one trailing terminator). A needle
+    // built with `encrypt_padded` carries extra trailing null blocks beyond
+    // its terminator that have no corresponding plaintext length to read
+    // off homomorphically without decrypting, so they'd be matched as
+    // literal null characters instead of being excluded — refuse a padded
+    // `cipher` outright rather than silently matching the wrong window.
+    fn contains(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        assert!(!cipher.padded, "contains: needle must be terminator-only (encrypt), not encrypt_padded");
+        let needle_len = cipher.blocks.len().saturating_sub(1);
+        let needle: Vec<RadixCiphertext> = cipher.blocks[..needle_len].iter()
+            .map(|b| b.block.clone())
+            .collect();
+
+        return Self::tree_or(self.match_bits(&needle, ctx), ctx);
+    }
 
-                    }else{
-                        return sk.create_trivial_radix(0u64, FheAscii::NUM_BLOCKS);
-                    }
-                }else{
-                    return res;
-                }
-            }else {
-                return sk.create_trivial_radix(0u64, FheAscii::NUM_BLOCKS);
-            }
+    // See `contains`: `cipher` must be terminator-only (built with
+    // `encrypt`), not `encrypt_padded`.
+    fn find(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        assert!(!cipher.padded, "find: needle must be terminator-only (encrypt), not encrypt_padded");
+        let needle_len = cipher.blocks.len().saturating_sub(1);
+        let needle: Vec<RadixCiphertext> = cipher.blocks[..needle_len].iter()
+            .map(|b| b.block.clone())
+            .collect();
+
+        let bits = self.match_bits(&needle, ctx);
+        return self.find_from_bits(bits, ctx);
+    }
+
+    // See `contains`: `cipher` must be terminator-only (built with
+    // `encrypt`), not `encrypt_padded`. Unlike `contains`/`find`, `self`
+    // must be terminator-only too: the window is anchored off
+    // `self.blocks.len()`, so a padded `self` would land the comparison
+    // inside the trailing null padding instead of at the string's true
+    // end and almost always wrongly return false — refuse both rather
+    // than return that wrong answer.
+    fn ends_with(&self, cipher: &FheString, ctx: &FheStringContext) -> RadixCiphertext {
+        assert!(!cipher.padded, "ends_with: needle must be terminator-only (encrypt), not encrypt_padded");
+        assert!(!self.padded, "ends_with: haystack must be terminator-only (encrypt), not encrypt_padded");
+        let sk = &ctx.sk;
+        let needle_len = cipher.blocks.len().saturating_sub(1);
+        if self.blocks.len().saturating_sub(1) < needle_len {
+            return sk.create_trivial_radix(0u64, ctx.num_blocks);
+        }
+
+        // Exclude `self`'s own terminator from the compared window: the
+        // last `needle_len` characters sit just before it, not at the tail
+        // of `self.blocks` itself.
+        let start = self.blocks.len() - 1 - needle_len;
+        let bits: Vec<RadixCiphertext> = self.blocks[start..start + needle_len].par_iter()
+            .zip(cipher.blocks[..needle_len].par_iter())
+            .map(|(item1, item2)| {
+                let mut ip1 = item1.block.clone();
+                let mut ip2 = item2.block.clone();
+                sk.smart_eq_parallelized(&mut ip1, &mut ip2)
+            })
+            .collect();
+
+        return Self::tree_and(bits, ctx);
+    }
+
+    // Drops `self`'s terminator before appending, so the result holds no
+    // embedded '\0' and `decrypt` shows both parts. A padded `self` keeps
+    // its null padding, so its appended content sits after a '\0' and
+    // `decrypt` shows only `self`'s part.
+    fn concat_assign(&mut self, cipher: &FheString) -> &Self {
+        if !self.padded {
+            self.blocks.pop();
+        }
+        self.blocks.extend(cipher.blocks.clone());
+        self.padded = self.padded || cipher.padded;
+        return self;
+    }
+
+    fn starts_with(&self, cipher: &FheString, ctx: &FheStringContext)
+        -> RadixCiphertext {
+        let sk = &ctx.sk;
+        // `cipher` includes a trailing null terminator, which isn't part of
+        // the needle being matched against.
+        let needle_len = cipher.blocks.len().saturating_sub(1);
+        if self.blocks.len() < needle_len {
+            return sk.create_trivial_radix(0u64, ctx.num_blocks);
         }
+
+        let bits: Vec<RadixCiphertext> = self.blocks[..needle_len].par_iter()
+            .zip(cipher.blocks[..needle_len].par_iter())
+            .map(|(item1, item2)| {
+                let mut ip1 = item1.block.clone();
+                let mut ip2 = item2.block.clone();
+                sk.smart_eq_parallelized(&mut ip1, &mut ip2)
+            })
+            .collect();
+
+        return Self::tree_and(bits, ctx);
     }
 
-    // fn eq_ignore_case(&self, cipher: &FheString, sk: &ServerKey) 
+    fn starts_with_plain(&self, string: &str, ctx: &FheStringContext)
+        -> RadixCiphertext {
+        let sk = &ctx.sk;
+        let chars: Vec<char> = string.chars().collect();
+        let needle_len = chars.len();
+        if self.blocks.len() < needle_len {
+            return sk.create_trivial_radix(0u64, ctx.num_blocks);
+        }
+
+        let bits: Vec<RadixCiphertext> = self.blocks[..needle_len].par_iter()
+            .zip(chars.par_iter())
+            .map(|(item, c)| {
+                let mut ip = item.block.clone();
+                sk.smart_scalar_eq_parallelized(&mut ip, *c as u64)
+            })
+            .collect();
+
+        return Self::tree_and(bits, ctx);
+    }
+
+    // fn eq_ignore_case(&self, cipher: &FheString, ctx: &FheStringContext)
     //     -> &Self{
-        
-    //     let 
+
+    //     let
     // }
 }
 
 fn main(){
-    let num_blocks = 4;
-    let (ck, sk) = gen_keys_radix(PARAM_MESSAGE_2_CARRY_2_KS_PBS, num_blocks);
-    let input = "Hi, Divyesh's Ph no: 7987267463.\0 \nRole-Researcher@TCS Research.";
+    let ctx = FheStringContext::builder(CharParams::Message2Carry2)
+        .build_with_wopbs();
+    // No embedded '\0': the null-terminated representation (see
+    // `FheString::decrypt`) can't distinguish a literal NUL in the
+    // plaintext from end-of-string.
+    let input = "Hi, Divyesh's Ph no: 7987267463. \nRole-Researcher@TCS Research.";
 
     println!("Plain Input is:\n{}", input);
     println!();
 
     let mut now = Instant::now();
-    let mut enc_ip = FheString::encrypt(&input, &ck);
+    let mut enc_ip = FheString::encrypt(&input, &ctx);
     println!("Time to encrypt the input is {:?}", now.elapsed());
     println!();
 
     now = Instant::now();
-    let dec_ip = enc_ip.decrypt(&ck);
+    let dec_ip = enc_ip.decrypt(&ctx);
     println!("Time to decrypt input is {:?}", now.elapsed());
     println!("Decrypted Input is\n{}", dec_ip);
     println!();
 
     now = Instant::now();
-    let len = enc_ip.len(&sk);
-    println!("length of the input string is {}, time taken={:?}", 
-    ck.decrypt::<u64>(&len), now.elapsed());
-    // assert_eq!(ck.decrypt::<u64>(&len) as usize, input.len());
+    let len = enc_ip.len(&ctx);
+    println!("length of the input string is {}, time taken={:?}",
+    ctx.ck.decrypt::<u64>(&len), now.elapsed());
+    // assert_eq!(ctx.ck.decrypt::<u64>(&len) as usize, input.len());
     println!();
 
     now = Instant::now();
-    let empty_check = enc_ip.is_empty(&sk);
-    if ck.decrypt::<u64>(&empty_check) == 1 {
+    let empty_check = enc_ip.is_empty(&ctx);
+    if ctx.ck.decrypt::<u64>(&empty_check) == 1 {
         println!("String is empty, time taken to check is {:?}", now.elapsed());
     }else {
         println!("String is not empoty, time taken to check is {:?}", now.elapsed());
@@ -265,49 +711,166 @@ fn main(){
     println!();
 
     now = Instant::now();
-    enc_ip.to_lower_assign_parallelized(&sk);
+    enc_ip.to_lower_assign_parallelized(&ctx);
     println!("Lower case conversion of the input string is\n{}\n
-    Time taken to convert to lowercase is {:?}", enc_ip.decrypt(&ck), now.elapsed());
-    // assert_eq!(enc_ip.decrypt(&ck), input.to_lowercase());
+    Time taken to convert to lowercase is {:?}", enc_ip.decrypt(&ctx), now.elapsed());
+    // assert_eq!(enc_ip.decrypt(&ctx), input.to_lowercase());
     println!();
-    
+
     now = Instant::now();
-    enc_ip.to_upper_assign_parallelized(&sk);
+    enc_ip.to_upper_assign_parallelized(&ctx);
     println!("Upper case conversion of the input string is\n{}\n
-    Time taken to convert to uppercase is {:?}",enc_ip.decrypt(&ck), now.elapsed());
-    // assert_eq!(enc_ip.decrypt(&ck), input.to_uppercase());
+    Time taken to convert to uppercase is {:?}",enc_ip.decrypt(&ctx), now.elapsed());
+    // assert_eq!(enc_ip.decrypt(&ctx), input.to_uppercase());
+    println!();
+
+    now = Instant::now();
+    let mut rot13_ip = enc_ip.clone();
+    rot13_ip.to_ascii_rot13_assign(&ctx);
+    println!("ROT13 of the input string is\n{}\n
+    Time taken to rot13 is {:?}", rot13_ip.decrypt(&ctx), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let alphabetic_count: u64 = enc_ip.is_alphabetic(&ctx).iter()
+        .map(|b| ctx.ck.decrypt::<u64>(b))
+        .sum();
+    println!("{} alphabetic characters found in the input string, time taken={:?}",
+    alphabetic_count, now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let digit_count: u64 = enc_ip.is_digit(&ctx).iter()
+        .map(|b| ctx.ck.decrypt::<u64>(b))
+        .sum();
+    println!("{} digit characters found in the input string, time taken={:?}",
+    digit_count, now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let whitespace_count: u64 = enc_ip.is_whitespace(&ctx).iter()
+        .map(|b| ctx.ck.decrypt::<u64>(b))
+        .sum();
+    println!("{} whitespace characters found in the input string, time taken={:?}",
+    whitespace_count, now.elapsed());
     println!();
 
     let string1 = "Divyesh";
     let string2 = "DivyeshS";
-    let mut enc_str1 = FheString::encrypt(&string1, &ck);
-    let enc_str2 = FheString::encrypt(&string2, &ck);
+    let mut enc_str1 = FheString::encrypt(&string1, &ctx);
+    let enc_str2 = FheString::encrypt(&string2, &ctx);
 
     now = Instant::now();
-    let mut eq = enc_str1.equals(&enc_str2, &sk);
+    let mut eq = enc_str1.equals(&enc_str2, &ctx);
     println!("String {} and String {} encrypted equality condition is {}
-    Time taken to perform equality operation is {:?}", string1, string2, 
-    ck.decrypt::<u64>(&eq), now.elapsed());
+    Time taken to perform equality operation is {:?}", string1, string2,
+    ctx.ck.decrypt::<u64>(&eq), now.elapsed());
     println!();
 
     now = Instant::now();
-    eq = enc_str1.equals_plain(&string2, &sk);
+    eq = enc_str1.equals_plain(&string2, &ctx);
     println!("String {} and String {} plain string equality condition is {}
-    Time taken to perform equality operation is {:?}", string1, string2, 
-    ck.decrypt::<u64>(&eq), now.elapsed());
+    Time taken to perform equality operation is {:?}", string1, string2,
+    ctx.ck.decrypt::<u64>(&eq), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let neq = enc_str1.not_equals(&enc_str2, &ctx);
+    println!("String {} and String {} encrypted not_equals condition is {}
+    Time taken to perform not_equals operation is {:?}", string1, string2,
+    ctx.ck.decrypt::<u64>(&neq), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let neq = enc_str1.not_equals_plain(&string2, &ctx);
+    println!("String {} and String {} plain string not_equals condition is {}
+    Time taken to perform not_equals operation is {:?}", string1, string2,
+    ctx.ck.decrypt::<u64>(&neq), now.elapsed());
     println!();
 
     now = Instant::now();
     enc_str1.concat_assign(&enc_str2);
     println!("Concatenated string is {}
-    Time taken to concatenate is {:?}", enc_str1.decrypt(&ck), now.elapsed());
+    Time taken to concatenate is {:?}", enc_str1.decrypt(&ctx), now.elapsed());
     println!();
-    
+
     now = Instant::now();
-    let eq = enc_str1.starts_with(&enc_str2, &sk);
+    let eq = enc_str1.starts_with(&enc_str2, &ctx);
     println!("Condition to check String {} starts_with String {} = {}
-    Time taken to check is {:?}", enc_str1.decrypt(&ck), enc_str2.decrypt(&ck),
-     ck.decrypt::<u64>(&eq), now.elapsed());
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_str2.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&eq), now.elapsed());
+    println!();
+
+    let padded_string = "Divyesh";
+    let enc_padded = FheString::encrypt_padded(&padded_string, 16, &ctx);
+
+    now = Instant::now();
+    let padded_len = enc_padded.len(&ctx);
+    println!("Homomorphic length of the padded (16-block) string {} is {}, time taken={:?}",
+    padded_string, ctx.ck.decrypt::<u64>(&padded_len), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let lt = enc_str1.lt(&enc_str2, &ctx);
+    println!("Condition to check String {} lt String {} = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_str2.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&lt), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let gt = enc_str1.gt(&enc_str2, &ctx);
+    println!("Condition to check String {} gt String {} = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_str2.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&gt), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let le = enc_str1.le(&enc_str2, &ctx);
+    println!("Condition to check String {} le String {} = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_str2.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&le), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let ge = enc_str1.ge(&enc_str2, &ctx);
+    println!("Condition to check String {} ge String {} = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_str2.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&ge), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let found = enc_str1.find_plain("yesh", &ctx);
+    println!("Index of \"yesh\" in String {} is {} (capacity-sized sentinel means not found)
+    Time taken to find is {:?}", enc_str1.decrypt(&ctx), ctx.ck.decrypt::<u64>(&found), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let contains_plain = enc_str1.contains_plain("yesh", &ctx);
+    println!("String {} contains_plain \"yesh\" = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), ctx.ck.decrypt::<u64>(&contains_plain), now.elapsed());
+    println!();
+
+    let enc_needle = FheString::encrypt(&"yesh", &ctx);
+
+    now = Instant::now();
+    let contains = enc_str1.contains(&enc_needle, &ctx);
+    println!("String {} contains String {} = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_needle.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&contains), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let find = enc_str1.find(&enc_needle, &ctx);
+    println!("Index of String {} in String {} is {} (capacity-sized sentinel means not found)
+    Time taken to find is {:?}", enc_needle.decrypt(&ctx), enc_str1.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&find), now.elapsed());
+    println!();
+
+    now = Instant::now();
+    let ends_with = enc_str1.ends_with(&enc_needle, &ctx);
+    println!("String {} ends_with String {} = {}
+    Time taken to check is {:?}", enc_str1.decrypt(&ctx), enc_needle.decrypt(&ctx),
+     ctx.ck.decrypt::<u64>(&ends_with), now.elapsed());
     println!();
 
 }